@@ -0,0 +1,41 @@
+//! Compiles the `.cu` sources under `kernels/` to PTX at build time, once per supported
+//! compute capability, so that `cuda_backend` can embed the result and select a variant at
+//! runtime instead of calling into NVRTC on first use.
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Compute capabilities (virtual `compute_XX` architectures) we ship PTX for. `cuda_backend::JitTarget` mirrors this list; keep
+/// the two in sync when adding a new target.
+const TARGET_ARCHES: &[&str] = &["60", "70", "75", "80", "86"];
+
+const KERNELS: &[&str] = &["affine", "fill"];
+
+fn main() {
+    println!("cargo:rerun-if-changed=kernels");
+    let out_dir = PathBuf::from(std::env::var("OUT_DIR").unwrap());
+    let nvcc = std::env::var("NVCC").unwrap_or_else(|_| "nvcc".to_string());
+
+    for kernel in KERNELS {
+        let src = Path::new("kernels").join(format!("{kernel}.cu"));
+        println!("cargo:rerun-if-changed={}", src.display());
+        for arch in TARGET_ARCHES {
+            let dst = out_dir.join(format!("{kernel}_sm{arch}.ptx"));
+            let status = Command::new(&nvcc)
+                .arg("--ptx")
+                .arg(format!("-arch=compute_{arch}"))
+                .arg(&src)
+                .arg("-o")
+                .arg(&dst)
+                .status();
+            match status {
+                Ok(status) if status.success() => {}
+                Ok(status) => panic!("nvcc exited with {status} while compiling {}", src.display()),
+                Err(err) => panic!(
+                    "failed to run `{nvcc}` to compile {} to PTX ({err}); set the NVCC env \
+                     var if nvcc is not on PATH",
+                    src.display()
+                ),
+            }
+        }
+    }
+}
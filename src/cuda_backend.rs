@@ -1,5 +1,7 @@
 use crate::{CpuStorage, DType, Shape};
 use cudarc::driver::{CudaFunction, CudaSlice, LaunchAsync, LaunchConfig};
+use cudarc::nvrtc::Ptx;
+use half::{bf16, f16};
 
 /// cudarc related errors
 #[derive(thiserror::Error, Debug)]
@@ -7,174 +9,710 @@ pub enum CudaError {
     #[error(transparent)]
     Cuda(#[from] cudarc::driver::DriverError),
 
-    #[error(transparent)]
-    Compiler(#[from] cudarc::nvrtc::CompileError),
-
     #[error("{op} only supports contiguous tensors")]
     RequiresContiguous { op: &'static str },
 
     #[error("missing kernel '{module_name}'")]
     MissingKernel { module_name: &'static str },
+
+    #[error(
+        "device compute capability {found:?} is older than the oldest embedded PTX target \
+         {required:?}"
+    )]
+    UnsupportedArch {
+        found: (i32, i32),
+        required: JitTarget,
+    },
 }
 
 type Result<T> = std::result::Result<T, CudaError>;
 
+/// Optimization level for the CUDA driver's JIT compiler. It is not passed to the driver:
+/// `CudaDevice::load_ptx` loads modules without any JIT options, so the value chosen here has
+/// no effect on the compiled kernels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OptLevel {
+    O0,
+    O1,
+    O2,
+    O3,
+    O4,
+}
+
+impl Default for OptLevel {
+    fn default() -> Self {
+        Self::O4
+    }
+}
+
+/// Target compute capability for an embedded PTX variant, analogous to cust's `JitTarget`.
+/// Keep this in sync with the `TARGET_ARCHES` list in `build.rs`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum JitTarget {
+    Compute60,
+    Compute70,
+    Compute75,
+    Compute80,
+    Compute86,
+}
+
+impl JitTarget {
+    /// Ascending order, matching the order PTX variants are embedded in.
+    const ALL: &'static [Self] = &[
+        Self::Compute60,
+        Self::Compute70,
+        Self::Compute75,
+        Self::Compute80,
+        Self::Compute86,
+    ];
+
+    /// The embedded target closest to, but not newer than, the device's actual compute
+    /// capability. The driver can always JIT-upgrade PTX written for an older virtual
+    /// architecture, so this is safe even when there is no exact match.
+    fn for_device(major: i32, minor: i32) -> Result<Self> {
+        Self::ALL
+            .iter()
+            .rev()
+            .copied()
+            .find(|t| t.major_minor() <= (major, minor))
+            .ok_or(CudaError::UnsupportedArch {
+                found: (major, minor),
+                required: Self::ALL[0],
+            })
+    }
+
+    fn major_minor(self) -> (i32, i32) {
+        match self {
+            Self::Compute60 => (6, 0),
+            Self::Compute70 => (7, 0),
+            Self::Compute75 => (7, 5),
+            Self::Compute80 => (8, 0),
+            Self::Compute86 => (8, 6),
+        }
+    }
+}
+
+/// Embedded, build-time compiled PTX for a kernel, one variant per supported `JitTarget`.
+/// The `.ptx` files themselves are produced from `kernels/*.cu` by `build.rs`.
+struct Kernel {
+    module_name: &'static str,
+    ptx: [&'static str; JitTarget::ALL.len()],
+}
+
+macro_rules! kernel {
+    ($module_name:literal, $file:literal) => {
+        Kernel {
+            module_name: $module_name,
+            ptx: [
+                include_str!(concat!(env!("OUT_DIR"), "/", $file, "_sm60.ptx")),
+                include_str!(concat!(env!("OUT_DIR"), "/", $file, "_sm70.ptx")),
+                include_str!(concat!(env!("OUT_DIR"), "/", $file, "_sm75.ptx")),
+                include_str!(concat!(env!("OUT_DIR"), "/", $file, "_sm80.ptx")),
+                include_str!(concat!(env!("OUT_DIR"), "/", $file, "_sm86.ptx")),
+            ],
+        }
+    };
+}
+
+const AFFINE_F16: Kernel = kernel!("affine_f16", "affine");
+const AFFINE_BF16: Kernel = kernel!("affine_bf16", "affine");
+const AFFINE_F32: Kernel = kernel!("affine_f32", "affine");
+const AFFINE_F64: Kernel = kernel!("affine_f64", "affine");
+const FILL_F16: Kernel = kernel!("fill_f16", "fill");
+const FILL_BF16: Kernel = kernel!("fill_bf16", "fill");
+const FILL_F32: Kernel = kernel!("fill_f32", "fill");
+const FILL_F64: Kernel = kernel!("fill_f64", "fill");
+
+impl Kernel {
+    fn ptx_for(&self, target: JitTarget) -> &'static str {
+        let idx = JitTarget::ALL.iter().position(|t| *t == target).unwrap();
+        self.ptx[idx]
+    }
+}
+
+/// Per-device pool of freed buffers kept around for reuse instead of being handed back to the
+/// driver, to avoid paying a `cudaMalloc`/`cudaFree` round trip for every op that creates a
+/// same-shaped intermediate tensor. Buffers are bucketed by element count rounded up to the
+/// next power of two: every allocation path, on a pool miss, allocates the full bucket size (not
+/// just the requested count), so every buffer stored in a bucket is guaranteed large enough to
+/// serve any request that bucket can satisfy. Callers must track the logical element count
+/// separately (see `CudaStorage`), since a reused buffer's own length can be larger than what
+/// was asked for.
+///
+/// A buffer sitting in the pool is paired with the event recorded on its producing stream when
+/// it was recycled (see `Drop for CudaStorage`). Whoever takes it back out must wait on that
+/// event (on whichever stream will next use the buffer) before touching it, since the original
+/// producer may still have in-flight work targeting this memory. This avoids ever blocking the
+/// host thread just to recycle a buffer.
+type PooledEntry<T> = (cudarc::driver::CudaEvent, CudaSlice<T>);
+
+#[derive(Default)]
+struct Allocator {
+    f16: std::sync::Mutex<std::collections::HashMap<usize, Vec<PooledEntry<f16>>>>,
+    bf16: std::sync::Mutex<std::collections::HashMap<usize, Vec<PooledEntry<bf16>>>>,
+    f32: std::sync::Mutex<std::collections::HashMap<usize, Vec<PooledEntry<f32>>>>,
+    f64: std::sync::Mutex<std::collections::HashMap<usize, Vec<PooledEntry<f64>>>>,
+}
+
+fn alloc_bucket(elem_count: usize) -> usize {
+    elem_count.next_power_of_two()
+}
+
+/// Pops an entry from whichever bucket `elem_count` maps to, if any. Plain `HashMap`/`Vec`
+/// bookkeeping with no device calls, so the bucketing logic can be exercised without a GPU.
+fn bucket_take<T>(
+    buckets: &mut std::collections::HashMap<usize, Vec<T>>,
+    elem_count: usize,
+) -> Option<T> {
+    buckets
+        .get_mut(&alloc_bucket(elem_count))
+        .and_then(Vec::pop)
+}
+
+/// Stores `value` under the bucket for `capacity`. Callers must pass the buffer's actual
+/// allocated capacity here, not a possibly-smaller logical length, or a later request mapped to
+/// the same bucket can pop an entry too small to serve it.
+fn bucket_recycle<T>(
+    buckets: &mut std::collections::HashMap<usize, Vec<T>>,
+    capacity: usize,
+    value: T,
+) {
+    buckets
+        .entry(alloc_bucket(capacity))
+        .or_default()
+        .push(value);
+}
+
+macro_rules! allocator_bucket_methods {
+    ($take:ident, $recycle:ident, $field:ident, $dtype:ty) => {
+        fn $take(&self, elem_count: usize) -> Option<PooledEntry<$dtype>> {
+            bucket_take(&mut self.$field.lock().unwrap(), elem_count)
+        }
+
+        fn $recycle(&self, slice: CudaSlice<$dtype>, ready: cudarc::driver::CudaEvent) {
+            let capacity = slice.len();
+            bucket_recycle(&mut self.$field.lock().unwrap(), capacity, (ready, slice));
+        }
+    };
+}
+
+impl Allocator {
+    allocator_bucket_methods!(take_f16, recycle_f16, f16, f16);
+    allocator_bucket_methods!(take_bf16, recycle_bf16, bf16, bf16);
+    allocator_bucket_methods!(take_f32, recycle_f32, f32, f32);
+    allocator_bucket_methods!(take_f64, recycle_f64, f64, f64);
+
+    /// Drops every pooled buffer, releasing the underlying device memory back to the driver.
+    fn clear(&self) {
+        self.f16.lock().unwrap().clear();
+        self.bf16.lock().unwrap().clear();
+        self.f32.lock().unwrap().clear();
+        self.f64.lock().unwrap().clear();
+    }
+}
+
+/// Registry of one `Allocator` per device ordinal. Keyed by ordinal rather than owned by
+/// `CudaDevice` directly so that every `CudaDevice` handle for the same physical device (e.g.
+/// the ones reconstructed from a `CudaSlice` via `CudaStorage::device`) shares the same pool.
+static ALLOCATORS: std::sync::OnceLock<
+    std::sync::Mutex<std::collections::HashMap<usize, std::sync::Arc<Allocator>>>,
+> = std::sync::OnceLock::new();
+
+fn allocator_for(ordinal: usize) -> std::sync::Arc<Allocator> {
+    ALLOCATORS
+        .get_or_init(|| std::sync::Mutex::new(std::collections::HashMap::new()))
+        .lock()
+        .unwrap()
+        .entry(ordinal)
+        .or_insert_with(|| std::sync::Arc::new(Allocator::default()))
+        .clone()
+}
+
+/// A CUDA stream on which allocations, transfers and kernel launches can be enqueued without
+/// blocking the calling thread. Work enqueued on a stream runs in order with respect to other
+/// work on that same stream, but may overlap with work on other streams.
+///
+/// Any `CudaStorage` produced by an op that takes a stream (`zeros_impl`, `const_impl`,
+/// `cuda_from_cpu_storage`, `affine_impl`) is not safe to read back or drop until the stream it
+/// was produced on has been synchronized (see `CudaDevice::synchronize`); the synchronous entry
+/// points (`to_cpu_storage`, ...) do this for you.
 #[derive(Debug, Clone)]
-pub struct CudaDevice(std::sync::Arc<cudarc::driver::CudaDevice>);
+pub struct CudaStream(std::sync::Arc<cudarc::driver::CudaStream>);
 
-// TODO: Switch to pre-compiled PTX kernels rather than compiling on the fly.
-const AFFINE_CU: &str = r#"
-extern "C" __global__ void affine_f32( 
-    const size_t numel, 
-    const float *x,
-    float *y,
-    const float mul,
-    const float add
-) { 
-    unsigned int i = blockIdx.x * blockDim.x + threadIdx.x; 
-    if (i >= numel) { 
-        return; 
-    } 
-    y[i] = x[i] * mul + add;
-} 
-
-extern "C" __global__ void affine_f64( 
-    const size_t numel, 
-    const double *x,
-    double *y,
-    const double mul,
-    const double add
-) { 
-    unsigned int i = blockIdx.x * blockDim.x + threadIdx.x; 
-    if (i >= numel) { 
-        return; 
-    } 
-    y[i] = x[i] * mul + add;
-} 
-"#;
-
-const FILL_CU: &str = r#"
-template<typename T>
-__device__ void fill_with(T *buf, T value, const size_t numel) {
-    for (unsigned int i = blockIdx.x * blockDim.x + threadIdx.x; i < numel; i += blockDim.x * gridDim.x) {
-        buf[i] = value;
-    }
-}
-extern "C" __global__ void fill_f16(__half *buf, __half value, const size_t numel) { fill_with(buf, value, numel); }
-extern "C" __global__ void fill_f32(float *buf, float value, const size_t numel) { fill_with(buf, value, numel); }
-extern "C" __global__ void fill_f64(double *buf, double value, const size_t numel) { fill_with(buf, value, numel); }
-"#;
+#[derive(Debug, Clone)]
+pub struct CudaDevice(std::sync::Arc<cudarc::driver::CudaDevice>);
 
 impl CudaDevice {
     pub(crate) fn new(ordinal: usize) -> Result<Self> {
         let device = cudarc::driver::CudaDevice::new(ordinal)?;
+        // Resolve the embedded PTX variant up front so that a device with no matching (or
+        // older) target fails fast at construction time rather than on first kernel launch.
+        let _ = Self(device.clone()).jit_target()?;
         Ok(Self(device))
     }
 
+    /// Creates a new stream that work can be enqueued on independently of the device's default
+    /// stream.
+    pub fn new_stream(&self) -> Result<CudaStream> {
+        Ok(CudaStream(self.0.fork_default_stream()?))
+    }
+
+    /// Blocks the calling thread until all work enqueued on `stream` has completed. Must be
+    /// called before reading back or dropping any `CudaStorage` produced on `stream`.
+    pub fn synchronize(&self, stream: &CudaStream) -> Result<()> {
+        stream.0.synchronize()?;
+        Ok(())
+    }
+
+    /// The embedded PTX target for the device's actual compute capability, which is queried
+    /// once per call since `CudaDevice` is a thin, cheaply-cloned handle and doesn't otherwise
+    /// cache device state.
+    fn jit_target(&self) -> Result<JitTarget> {
+        let major = self.0.attribute(
+            cudarc::driver::sys::CUdevice_attribute::CU_DEVICE_ATTRIBUTE_COMPUTE_CAPABILITY_MAJOR,
+        )?;
+        let minor = self.0.attribute(
+            cudarc::driver::sys::CUdevice_attribute::CU_DEVICE_ATTRIBUTE_COMPUTE_CAPABILITY_MINOR,
+        )?;
+        JitTarget::for_device(major, minor)
+    }
+
     pub(crate) fn ordinal(&self) -> usize {
         self.0.ordinal()
     }
 
-    pub(crate) fn zeros_impl(&self, shape: &Shape, dtype: DType) -> Result<CudaStorage> {
+    /// Releases every buffer this device's caching allocator is holding back to the driver.
+    /// Call this under memory pressure; it does not affect buffers currently in use.
+    pub fn empty_cache(&self) {
+        allocator_for(self.ordinal()).clear()
+    }
+
+    /// Enqueues the allocation on `stream`; the returned `CudaStorage` is not safe to read back
+    /// or drop until `stream` has been synchronized. Reuses a pooled buffer of adequate size
+    /// when one is available, zeroing it in place, rather than always asking the driver for
+    /// fresh memory.
+    pub(crate) fn zeros_impl(
+        &self,
+        shape: &Shape,
+        dtype: DType,
+        stream: &CudaStream,
+    ) -> Result<CudaStorage> {
         let elem_count = shape.elem_count();
+        let allocator = allocator_for(self.ordinal());
         match dtype {
+            DType::F16 => {
+                let data = match allocator.take_f16(elem_count) {
+                    Some((ready, mut data)) => {
+                        stream.0.wait_for_event(&ready)?;
+                        self.0.memset_zeros_async(&mut data, &stream.0)?;
+                        data
+                    }
+                    None => self
+                        .0
+                        .alloc_zeros_async::<f16>(alloc_bucket(elem_count), &stream.0)?,
+                };
+                Ok(CudaStorage::F16(
+                    std::mem::ManuallyDrop::new(data),
+                    stream.clone(),
+                    elem_count,
+                ))
+            }
+            DType::BF16 => {
+                let data = match allocator.take_bf16(elem_count) {
+                    Some((ready, mut data)) => {
+                        stream.0.wait_for_event(&ready)?;
+                        self.0.memset_zeros_async(&mut data, &stream.0)?;
+                        data
+                    }
+                    None => self
+                        .0
+                        .alloc_zeros_async::<bf16>(alloc_bucket(elem_count), &stream.0)?,
+                };
+                Ok(CudaStorage::BF16(
+                    std::mem::ManuallyDrop::new(data),
+                    stream.clone(),
+                    elem_count,
+                ))
+            }
             DType::F32 => {
-                let data = self.0.alloc_zeros::<f32>(elem_count)?;
-                Ok(CudaStorage::F32(data))
+                let data = match allocator.take_f32(elem_count) {
+                    Some((ready, mut data)) => {
+                        stream.0.wait_for_event(&ready)?;
+                        self.0.memset_zeros_async(&mut data, &stream.0)?;
+                        data
+                    }
+                    None => self
+                        .0
+                        .alloc_zeros_async::<f32>(alloc_bucket(elem_count), &stream.0)?,
+                };
+                Ok(CudaStorage::F32(
+                    std::mem::ManuallyDrop::new(data),
+                    stream.clone(),
+                    elem_count,
+                ))
             }
             DType::F64 => {
-                let data = self.0.alloc_zeros::<f64>(elem_count)?;
-                Ok(CudaStorage::F64(data))
+                let data = match allocator.take_f64(elem_count) {
+                    Some((ready, mut data)) => {
+                        stream.0.wait_for_event(&ready)?;
+                        self.0.memset_zeros_async(&mut data, &stream.0)?;
+                        data
+                    }
+                    None => self
+                        .0
+                        .alloc_zeros_async::<f64>(alloc_bucket(elem_count), &stream.0)?,
+                };
+                Ok(CudaStorage::F64(
+                    std::mem::ManuallyDrop::new(data),
+                    stream.clone(),
+                    elem_count,
+                ))
             }
         }
     }
 
-    pub(crate) fn const_impl(&self, v: f64, shape: &Shape, dtype: DType) -> Result<CudaStorage> {
+    /// Enqueues the allocation and fill kernel on `stream` without blocking; the returned
+    /// `CudaStorage` is not safe to read back or drop until `stream` has been synchronized.
+    /// Reuses a pooled buffer of adequate size when one is available instead of always asking
+    /// the driver for fresh memory; the fill kernel overwrites it regardless of its prior
+    /// contents.
+    pub(crate) fn const_impl(
+        &self,
+        v: f64,
+        shape: &Shape,
+        dtype: DType,
+        stream: &CudaStream,
+    ) -> Result<CudaStorage> {
         let elem_count = shape.elem_count();
         let cfg = LaunchConfig::for_num_elems(elem_count as u32);
         let dev = &self.0;
+        let allocator = allocator_for(self.ordinal());
         match dtype {
+            DType::F16 => {
+                // SAFETY: Set later by running the fill kernel on the same stream.
+                let data = match allocator.take_f16(elem_count) {
+                    Some((ready, data)) => {
+                        stream.0.wait_for_event(&ready)?;
+                        data
+                    }
+                    None => unsafe { dev.alloc_async::<f16>(alloc_bucket(elem_count), &stream.0) }?,
+                };
+                let func = self.load_ptx(&FILL_F16)?;
+                let params = (&data, f16::from_f64(v), elem_count);
+                unsafe { func.launch_on_stream(&stream.0, cfg, params) }?;
+                Ok(CudaStorage::F16(
+                    std::mem::ManuallyDrop::new(data),
+                    stream.clone(),
+                    elem_count,
+                ))
+            }
+            DType::BF16 => {
+                // SAFETY: Set later by running the fill kernel on the same stream.
+                let data = match allocator.take_bf16(elem_count) {
+                    Some((ready, data)) => {
+                        stream.0.wait_for_event(&ready)?;
+                        data
+                    }
+                    None => {
+                        unsafe { dev.alloc_async::<bf16>(alloc_bucket(elem_count), &stream.0) }?
+                    }
+                };
+                let func = self.load_ptx(&FILL_BF16)?;
+                let params = (&data, bf16::from_f64(v), elem_count);
+                unsafe { func.launch_on_stream(&stream.0, cfg, params) }?;
+                Ok(CudaStorage::BF16(
+                    std::mem::ManuallyDrop::new(data),
+                    stream.clone(),
+                    elem_count,
+                ))
+            }
             DType::F32 => {
-                // SAFETY: Set later by running the fill kernel.
-                let data = unsafe { dev.alloc::<f32>(elem_count) }?;
-                let func = self.get_or_load_func("fill_f32", FILL_CU)?;
+                // SAFETY: Set later by running the fill kernel on the same stream.
+                let data = match allocator.take_f32(elem_count) {
+                    Some((ready, data)) => {
+                        stream.0.wait_for_event(&ready)?;
+                        data
+                    }
+                    None => unsafe { dev.alloc_async::<f32>(alloc_bucket(elem_count), &stream.0) }?,
+                };
+                let func = self.load_ptx(&FILL_F32)?;
                 let params = (&data, v as f32, elem_count);
-                unsafe { func.launch(cfg, params) }?;
-                Ok(CudaStorage::F32(data))
+                unsafe { func.launch_on_stream(&stream.0, cfg, params) }?;
+                Ok(CudaStorage::F32(
+                    std::mem::ManuallyDrop::new(data),
+                    stream.clone(),
+                    elem_count,
+                ))
             }
             DType::F64 => {
-                // SAFETY: Set later by running the fill kernel.
-                let data = unsafe { dev.alloc::<f64>(elem_count) }?;
-                let func = self.get_or_load_func("fill_f64", FILL_CU)?;
+                // SAFETY: Set later by running the fill kernel on the same stream.
+                let data = match allocator.take_f64(elem_count) {
+                    Some((ready, data)) => {
+                        stream.0.wait_for_event(&ready)?;
+                        data
+                    }
+                    None => unsafe { dev.alloc_async::<f64>(alloc_bucket(elem_count), &stream.0) }?,
+                };
+                let func = self.load_ptx(&FILL_F64)?;
                 let params = (&data, v, elem_count);
-                unsafe { func.launch(cfg, params) }?;
-                Ok(CudaStorage::F64(data))
+                unsafe { func.launch_on_stream(&stream.0, cfg, params) }?;
+                Ok(CudaStorage::F64(
+                    std::mem::ManuallyDrop::new(data),
+                    stream.clone(),
+                    elem_count,
+                ))
             }
         }
     }
 
-    pub(crate) fn ones_impl(&self, shape: &Shape, dtype: DType) -> Result<CudaStorage> {
-        self.const_impl(1., shape, dtype)
+    pub(crate) fn ones_impl(
+        &self,
+        shape: &Shape,
+        dtype: DType,
+        stream: &CudaStream,
+    ) -> Result<CudaStorage> {
+        self.const_impl(1., shape, dtype, stream)
     }
 
-    pub(crate) fn cuda_from_cpu_storage(&self, storage: &CpuStorage) -> Result<CudaStorage> {
+    /// Enqueues the host-to-device copy on `stream`; the returned `CudaStorage` is not safe to
+    /// read back or drop until `stream` has been synchronized. Like `zeros_impl`/`const_impl`,
+    /// reuses a pooled buffer when one is available instead of always asking the driver for
+    /// fresh memory, copying host data into only the first `storage.len()` elements; a pool miss
+    /// allocates the full `alloc_bucket(storage.len())` size rather than the exact length, so
+    /// this buffer is safe to later recycle under its bucket like any other.
+    pub(crate) fn cuda_from_cpu_storage(
+        &self,
+        storage: &CpuStorage,
+        stream: &CudaStream,
+    ) -> Result<CudaStorage> {
+        let allocator = allocator_for(self.ordinal());
         match storage {
+            CpuStorage::F16(storage) => {
+                let len = storage.len();
+                let mut data = match allocator.take_f16(len) {
+                    Some((ready, data)) => {
+                        stream.0.wait_for_event(&ready)?;
+                        data
+                    }
+                    None => unsafe { self.0.alloc_async::<f16>(alloc_bucket(len), &stream.0) }?,
+                };
+                self.0.htod_async_copy_into(
+                    storage.clone(),
+                    &mut data.slice_mut(..len),
+                    &stream.0,
+                )?;
+                Ok(CudaStorage::F16(
+                    std::mem::ManuallyDrop::new(data),
+                    stream.clone(),
+                    len,
+                ))
+            }
+            CpuStorage::BF16(storage) => {
+                let len = storage.len();
+                let mut data = match allocator.take_bf16(len) {
+                    Some((ready, data)) => {
+                        stream.0.wait_for_event(&ready)?;
+                        data
+                    }
+                    None => unsafe { self.0.alloc_async::<bf16>(alloc_bucket(len), &stream.0) }?,
+                };
+                self.0.htod_async_copy_into(
+                    storage.clone(),
+                    &mut data.slice_mut(..len),
+                    &stream.0,
+                )?;
+                Ok(CudaStorage::BF16(
+                    std::mem::ManuallyDrop::new(data),
+                    stream.clone(),
+                    len,
+                ))
+            }
             CpuStorage::F32(storage) => {
-                let data = self.0.htod_sync_copy(storage)?;
-                Ok(CudaStorage::F32(data))
+                let len = storage.len();
+                let mut data = match allocator.take_f32(len) {
+                    Some((ready, data)) => {
+                        stream.0.wait_for_event(&ready)?;
+                        data
+                    }
+                    None => unsafe { self.0.alloc_async::<f32>(alloc_bucket(len), &stream.0) }?,
+                };
+                self.0.htod_async_copy_into(
+                    storage.clone(),
+                    &mut data.slice_mut(..len),
+                    &stream.0,
+                )?;
+                Ok(CudaStorage::F32(
+                    std::mem::ManuallyDrop::new(data),
+                    stream.clone(),
+                    len,
+                ))
             }
             CpuStorage::F64(storage) => {
-                let data = self.0.htod_sync_copy(storage)?;
-                Ok(CudaStorage::F64(data))
+                let len = storage.len();
+                let mut data = match allocator.take_f64(len) {
+                    Some((ready, data)) => {
+                        stream.0.wait_for_event(&ready)?;
+                        data
+                    }
+                    None => unsafe { self.0.alloc_async::<f64>(alloc_bucket(len), &stream.0) }?,
+                };
+                self.0.htod_async_copy_into(
+                    storage.clone(),
+                    &mut data.slice_mut(..len),
+                    &stream.0,
+                )?;
+                Ok(CudaStorage::F64(
+                    std::mem::ManuallyDrop::new(data),
+                    stream.clone(),
+                    len,
+                ))
             }
         }
     }
 
-    fn get_or_load_func(
-        &self,
-        module_name: &'static str,
-        source: &'static str,
-    ) -> Result<CudaFunction> {
+    /// Loads the embedded PTX variant matching this device's compute capability (or the
+    /// nearest older one, which the driver JIT-upgrades), and returns the requested kernel
+    /// function. A no-op if the module is already loaded.
+    ///
+    /// Note: `OptLevel` has no effect here. cudarc's safe `load_ptx` doesn't expose a way to
+    /// pass `CUjit_option`s through to the driver's JIT compiler, so there is currently nothing
+    /// to apply it to; it's kept as public API in case a future cudarc version (or a drop down
+    /// to the `sys` bindings) adds that hook back.
+    fn load_ptx(&self, kernel: &Kernel) -> Result<CudaFunction> {
         let dev = &self.0;
-        if !dev.has_func(module_name, module_name) {
-            // TODO: Pre-compile and load rather than compiling here.
-            let ptx = cudarc::nvrtc::compile_ptx(source)?;
-            dev.load_ptx(ptx, module_name, &[module_name])?;
+        if !dev.has_func(kernel.module_name, kernel.module_name) {
+            let ptx = Ptx::from_src(kernel.ptx_for(self.jit_target()?));
+            dev.load_ptx(ptx, kernel.module_name, &[kernel.module_name])?;
         }
-        dev.get_func(module_name, module_name)
+        dev.get_func(kernel.module_name, kernel.module_name)
             // Clippy recommends this `ok_or` rather than `ok_or_else` so hopefully the compiler is
             // able to only build the error value if needed.
-            .ok_or(CudaError::MissingKernel { module_name })
+            .ok_or(CudaError::MissingKernel {
+                module_name: kernel.module_name,
+            })
+    }
+}
+
+/// A host buffer produced by an async device-to-host copy (`CudaStorage::to_cpu_storage_async`).
+/// The bytes are only valid once the producing stream has synchronized; call `wait` to get the
+/// resolved `CpuStorage`.
+pub struct PendingCpuStorage {
+    storage: CpuStorage,
+    stream: CudaStream,
+}
+
+impl PendingCpuStorage {
+    /// Synchronizes the producing stream and returns the now-valid host storage.
+    pub fn wait(self, device: &CudaDevice) -> Result<CpuStorage> {
+        device.synchronize(&self.stream)?;
+        Ok(self.storage)
     }
 }
 
+/// The `usize` on each variant is the logical element count (i.e. `shape.elem_count()`) the
+/// storage was created for. It can be smaller than the wrapped `CudaSlice`'s own `.len()`, since
+/// a pooled buffer is allocated at its bucket's rounded-up size (see `Allocator`) and may be
+/// reused for a request asking for fewer elements than it actually holds; every caller-visible
+/// operation must use the logical count, never the slice's physical length.
 #[derive(Debug, Clone)]
 pub enum CudaStorage {
-    F32(CudaSlice<f32>),
-    F64(CudaSlice<f64>),
+    F16(std::mem::ManuallyDrop<CudaSlice<f16>>, CudaStream, usize),
+    BF16(std::mem::ManuallyDrop<CudaSlice<bf16>>, CudaStream, usize),
+    F32(std::mem::ManuallyDrop<CudaSlice<f32>>, CudaStream, usize),
+    F64(std::mem::ManuallyDrop<CudaSlice<f64>>, CudaStream, usize),
+}
+
+/// Returns freed buffers to the owning device's pool instead of letting the driver free them,
+/// so a later alloc of the same size can reuse them. See `Allocator`.
+///
+/// A recycled buffer can be popped by any stream on the same device, with no ordering
+/// relationship to whatever was still in flight on the producing `stream`. Rather than blocking
+/// the calling thread on `stream.synchronize()` here (which would serialize every single tensor
+/// drop and defeat the overlap CUDA streams exist for), an event is recorded on `stream` instead
+/// and stored alongside the buffer; whoever takes the buffer back out of the pool waits on that
+/// event on their own stream before reusing it (see `Allocator::take_f16` callers), which is
+/// enough to prevent the reuse race without ever blocking the host.
+impl Drop for CudaStorage {
+    fn drop(&mut self) {
+        match self {
+            Self::F16(slice, stream, _) => {
+                // SAFETY: `slice` is never used again; `drop` is the last place this field is
+                // observed.
+                let slice = unsafe { std::mem::ManuallyDrop::take(slice) };
+                match stream.0.record_event(None) {
+                    Ok(ready) => allocator_for(slice.device().ordinal()).recycle_f16(slice, ready),
+                    // Couldn't record an event to order a future reuse against; let the buffer
+                    // actually free instead of pooling it under an unknown readiness state.
+                    Err(_) => drop(slice),
+                }
+            }
+            Self::BF16(slice, stream, _) => {
+                // See the F16 arm above.
+                let slice = unsafe { std::mem::ManuallyDrop::take(slice) };
+                match stream.0.record_event(None) {
+                    Ok(ready) => allocator_for(slice.device().ordinal()).recycle_bf16(slice, ready),
+                    Err(_) => drop(slice),
+                }
+            }
+            Self::F32(slice, stream, _) => {
+                // See the F16 arm above.
+                let slice = unsafe { std::mem::ManuallyDrop::take(slice) };
+                match stream.0.record_event(None) {
+                    Ok(ready) => allocator_for(slice.device().ordinal()).recycle_f32(slice, ready),
+                    Err(_) => drop(slice),
+                }
+            }
+            Self::F64(slice, stream, _) => {
+                // See the F16 arm above.
+                let slice = unsafe { std::mem::ManuallyDrop::take(slice) };
+                match stream.0.record_event(None) {
+                    Ok(ready) => allocator_for(slice.device().ordinal()).recycle_f64(slice, ready),
+                    Err(_) => drop(slice),
+                }
+            }
+        }
+    }
 }
 
 impl CudaStorage {
     pub fn dtype(&self) -> DType {
         match self {
-            Self::F32(_) => DType::F32,
-            Self::F64(_) => DType::F64,
+            Self::F16(..) => DType::F16,
+            Self::BF16(..) => DType::BF16,
+            Self::F32(..) => DType::F32,
+            Self::F64(..) => DType::F64,
         }
     }
 
     pub fn device(&self) -> CudaDevice {
         match self {
-            Self::F32(slice) => CudaDevice(slice.device()),
-            Self::F64(slice) => CudaDevice(slice.device()),
+            Self::F16(slice, _, _) => CudaDevice(slice.device()),
+            Self::BF16(slice, _, _) => CudaDevice(slice.device()),
+            Self::F32(slice, _, _) => CudaDevice(slice.device()),
+            Self::F64(slice, _, _) => CudaDevice(slice.device()),
         }
     }
 
+    /// The stream this storage's contents were last written on. Any further op reading this
+    /// storage must either run on this same stream or synchronize it first.
+    pub fn stream(&self) -> &CudaStream {
+        match self {
+            Self::F16(_, stream, _) => stream,
+            Self::BF16(_, stream, _) => stream,
+            Self::F32(_, stream, _) => stream,
+            Self::F64(_, stream, _) => stream,
+        }
+    }
+
+    /// Enqueues the affine kernel on `stream` without blocking; the returned `CudaStorage` is
+    /// not safe to read back or drop until `stream` has been synchronized.
     pub(crate) fn affine_impl(
         &self,
         shape: &Shape,
         stride: &[usize],
         mul: f64,
         add: f64,
+        stream: &CudaStream,
     ) -> Result<Self> {
         if !shape.is_contiguous(stride) {
             return Err(CudaError::RequiresContiguous { op: "affine" });
@@ -183,42 +721,240 @@ impl CudaStorage {
         let elem_count = shape.elem_count();
         let cfg = LaunchConfig::for_num_elems(elem_count as u32);
         let dev = self.device();
+        let allocator = allocator_for(dev.ordinal());
         match self {
-            Self::F32(arg) => {
-                let func = dev.get_or_load_func("affine_f32", AFFINE_CU)?;
-                // SAFETY: if this function returns Ok(..), the kernel has been applied
-                // and has set the initially unset memory.
-                let out = unsafe { dev.0.alloc::<f32>(elem_count) }?;
-                let params = (elem_count, arg, &out, mul as f32, add as f32);
+            // The f16/bf16 kernels take `mul`/`add` as f32 and do the multiply-add in f32,
+            // downcasting only on store.
+            Self::F16(arg, _, _) => {
+                let func = dev.load_ptx(&AFFINE_F16)?;
+                let out = match allocator.take_f16(elem_count) {
+                    Some((ready, out)) => {
+                        stream.0.wait_for_event(&ready)?;
+                        out
+                    }
+                    // SAFETY: if this function returns Ok(..), the kernel has been applied
+                    // and has set the initially unset memory.
+                    None => unsafe {
+                        dev.0
+                            .alloc_async::<f16>(alloc_bucket(elem_count), &stream.0)
+                    }?,
+                };
+                let params = (elem_count, &**arg, &out, mul as f32, add as f32);
                 // SAFETY: well, well, well...
-                unsafe { func.launch(cfg, params) }?;
-                Ok(Self::F32(out))
-            }
-            Self::F64(arg) => {
-                let func = dev.get_or_load_func("affine_f64", AFFINE_CU)?;
-                // SAFETY: if this function returns Ok(..), the kernel has been applied
-                // and has set the initially unset memory.
-                let out = unsafe { dev.0.alloc::<f64>(elem_count) }?;
-                let params = (elem_count, arg, &out, mul, add);
+                unsafe { func.launch_on_stream(&stream.0, cfg, params) }?;
+                Ok(Self::F16(
+                    std::mem::ManuallyDrop::new(out),
+                    stream.clone(),
+                    elem_count,
+                ))
+            }
+            Self::BF16(arg, _, _) => {
+                let func = dev.load_ptx(&AFFINE_BF16)?;
+                let out = match allocator.take_bf16(elem_count) {
+                    Some((ready, out)) => {
+                        stream.0.wait_for_event(&ready)?;
+                        out
+                    }
+                    // SAFETY: if this function returns Ok(..), the kernel has been applied
+                    // and has set the initially unset memory.
+                    None => unsafe {
+                        dev.0
+                            .alloc_async::<bf16>(alloc_bucket(elem_count), &stream.0)
+                    }?,
+                };
+                let params = (elem_count, &**arg, &out, mul as f32, add as f32);
+                // SAFETY: well, well, well...
+                unsafe { func.launch_on_stream(&stream.0, cfg, params) }?;
+                Ok(Self::BF16(
+                    std::mem::ManuallyDrop::new(out),
+                    stream.clone(),
+                    elem_count,
+                ))
+            }
+            Self::F32(arg, _, _) => {
+                let func = dev.load_ptx(&AFFINE_F32)?;
+                let out = match allocator.take_f32(elem_count) {
+                    Some((ready, out)) => {
+                        stream.0.wait_for_event(&ready)?;
+                        out
+                    }
+                    // SAFETY: if this function returns Ok(..), the kernel has been applied
+                    // and has set the initially unset memory.
+                    None => unsafe {
+                        dev.0
+                            .alloc_async::<f32>(alloc_bucket(elem_count), &stream.0)
+                    }?,
+                };
+                let params = (elem_count, &**arg, &out, mul as f32, add as f32);
                 // SAFETY: well, well, well...
-                unsafe { func.launch(cfg, params) }?;
-                Ok(Self::F64(out))
+                unsafe { func.launch_on_stream(&stream.0, cfg, params) }?;
+                Ok(Self::F32(
+                    std::mem::ManuallyDrop::new(out),
+                    stream.clone(),
+                    elem_count,
+                ))
+            }
+            Self::F64(arg, _, _) => {
+                let func = dev.load_ptx(&AFFINE_F64)?;
+                let out = match allocator.take_f64(elem_count) {
+                    Some((ready, out)) => {
+                        stream.0.wait_for_event(&ready)?;
+                        out
+                    }
+                    // SAFETY: if this function returns Ok(..), the kernel has been applied
+                    // and has set the initially unset memory.
+                    None => unsafe {
+                        dev.0
+                            .alloc_async::<f64>(alloc_bucket(elem_count), &stream.0)
+                    }?,
+                };
+                let params = (elem_count, &**arg, &out, mul, add);
+                // SAFETY: well, well, well...
+                unsafe { func.launch_on_stream(&stream.0, cfg, params) }?;
+                Ok(Self::F64(
+                    std::mem::ManuallyDrop::new(out),
+                    stream.clone(),
+                    elem_count,
+                ))
             }
         }
     }
 
+    /// Synchronizes this storage's producing stream and copies it back to the host, so it is
+    /// safe to call while the op that produced the storage is still in flight.
     pub(crate) fn to_cpu_storage(&self) -> Result<CpuStorage> {
         match self {
-            Self::F32(slice) => {
+            // Copy only the logical `len` elements, not the slice's (possibly larger, pooled)
+            // physical capacity.
+            Self::F16(slice, stream, len) => {
+                let dev = slice.device();
+                stream.0.synchronize()?;
+                let cpu_storage = dev.dtoh_sync_copy(&slice.slice(..*len))?;
+                Ok(CpuStorage::F16(cpu_storage))
+            }
+            Self::BF16(slice, stream, len) => {
+                let dev = slice.device();
+                stream.0.synchronize()?;
+                let cpu_storage = dev.dtoh_sync_copy(&slice.slice(..*len))?;
+                Ok(CpuStorage::BF16(cpu_storage))
+            }
+            Self::F32(slice, stream, len) => {
                 let dev = slice.device();
-                let cpu_storage = dev.dtoh_sync_copy(slice)?;
+                stream.0.synchronize()?;
+                let cpu_storage = dev.dtoh_sync_copy(&slice.slice(..*len))?;
                 Ok(CpuStorage::F32(cpu_storage))
             }
-            Self::F64(slice) => {
+            Self::F64(slice, stream, len) => {
                 let dev = slice.device();
-                let cpu_storage = dev.dtoh_sync_copy(slice)?;
+                stream.0.synchronize()?;
+                let cpu_storage = dev.dtoh_sync_copy(&slice.slice(..*len))?;
                 Ok(CpuStorage::F64(cpu_storage))
             }
         }
     }
+
+    /// Enqueues the device-to-host copy on this storage's producing stream without blocking.
+    /// The returned `PendingCpuStorage` must be resolved with `PendingCpuStorage::wait` (which
+    /// synchronizes that stream) before its bytes are valid.
+    pub(crate) fn to_cpu_storage_async(&self) -> Result<PendingCpuStorage> {
+        match self {
+            // As in `to_cpu_storage`, `dst` is sized to the logical `len`, not the slice's
+            // physical capacity.
+            Self::F16(slice, stream, len) => {
+                let dev = slice.device();
+                let mut dst = vec![f16::ZERO; *len];
+                dev.dtoh_async(&slice.slice(..*len), &mut dst, &stream.0)?;
+                Ok(PendingCpuStorage {
+                    storage: CpuStorage::F16(dst),
+                    stream: stream.clone(),
+                })
+            }
+            Self::BF16(slice, stream, len) => {
+                let dev = slice.device();
+                let mut dst = vec![bf16::ZERO; *len];
+                dev.dtoh_async(&slice.slice(..*len), &mut dst, &stream.0)?;
+                Ok(PendingCpuStorage {
+                    storage: CpuStorage::BF16(dst),
+                    stream: stream.clone(),
+                })
+            }
+            Self::F32(slice, stream, len) => {
+                let dev = slice.device();
+                let mut dst = vec![0f32; *len];
+                dev.dtoh_async(&slice.slice(..*len), &mut dst, &stream.0)?;
+                Ok(PendingCpuStorage {
+                    storage: CpuStorage::F32(dst),
+                    stream: stream.clone(),
+                })
+            }
+            Self::F64(slice, stream, len) => {
+                let dev = slice.device();
+                let mut dst = vec![0f64; *len];
+                dev.dtoh_async(&slice.slice(..*len), &mut dst, &stream.0)?;
+                Ok(PendingCpuStorage {
+                    storage: CpuStorage::F64(dst),
+                    stream: stream.clone(),
+                })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn alloc_bucket_rounds_up_to_next_power_of_two() {
+        assert_eq!(alloc_bucket(0), 1);
+        assert_eq!(alloc_bucket(1), 1);
+        assert_eq!(alloc_bucket(2), 2);
+        assert_eq!(alloc_bucket(3), 4);
+        assert_eq!(alloc_bucket(1000), 1024);
+        assert_eq!(alloc_bucket(1024), 1024);
+        assert_eq!(alloc_bucket(1025), 2048);
+    }
+
+    #[test]
+    fn jit_target_for_device_picks_exact_match() {
+        let (major, minor) = JitTarget::Compute75.major_minor();
+        assert_eq!(
+            JitTarget::for_device(major, minor).unwrap(),
+            JitTarget::Compute75
+        );
+    }
+
+    #[test]
+    fn jit_target_for_device_falls_back_to_nearest_older() {
+        // 7.2 has no embedded target; the driver can JIT-upgrade from the nearest older one.
+        assert_eq!(JitTarget::for_device(7, 2).unwrap(), JitTarget::Compute70);
+    }
+
+    #[test]
+    fn jit_target_for_device_errors_below_oldest_target() {
+        let (major, minor) = JitTarget::Compute60.major_minor();
+        assert!(JitTarget::for_device(major, minor - 1).is_err());
+    }
+
+    #[test]
+    fn bucket_take_recycle_round_trips_within_a_bucket() {
+        let mut buckets = std::collections::HashMap::new();
+        bucket_recycle(&mut buckets, 1024, "buf-1024");
+        assert_eq!(bucket_take(&mut buckets, 1000), Some("buf-1024"));
+        assert_eq!(bucket_take(&mut buckets, 1000), None);
+    }
+
+    #[test]
+    fn bucket_recycle_must_be_called_with_the_buffer_s_actual_capacity() {
+        // Regression test for the bug where a buffer was recycled keyed off a logical length
+        // instead of its real allocated capacity: recycling a 1000-capacity buffer under bucket
+        // 1000 (rather than `alloc_bucket(1000) == 1024`) would let a later 1020-element request,
+        // which maps to the same 1024 bucket, pop a buffer too small to serve it.
+        let mut buckets = std::collections::HashMap::new();
+        bucket_recycle(&mut buckets, 1000, "undersized");
+        assert_eq!(bucket_take(&mut buckets, 1020), None);
+        // Recycled under its real capacity, the buffer sits in the 1000 bucket only, and a
+        // request that actually maps there can still find it.
+        assert_eq!(bucket_take(&mut buckets, 1000), Some("undersized"));
+    }
 }